@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use rstar::{Point, PointDistance, RTreeNum, RTreeObject, AABB};
+
+/// A point in `N`-dimensional space over scalar `S`, tagged with the
+/// [`Entity`] it was sampled from. The point type actually stored in an
+/// R-tree or VP-tree backend.
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialPoint<S, const N: usize> {
+    pub coords: [S; N],
+    pub entity: Entity,
+}
+
+impl<S, const N: usize> PartialEq for SpatialPoint<S, N> {
+    /// Only the entity identifies a point for rstar's `remove` - see
+    /// [`EntityPoint3D`]'s `From<Entity>` impl below for why.
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+
+impl<S, const N: usize> RTreeObject for SpatialPoint<S, N>
+where
+    S: RTreeNum,
+    [S; N]: Point<Scalar = S>,
+{
+    type Envelope = AABB<[S; N]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.coords)
+    }
+}
+
+impl<S, const N: usize> PointDistance for SpatialPoint<S, N>
+where
+    S: RTreeNum,
+    [S; N]: Point<Scalar = S>,
+{
+    fn distance_2(&self, point: &[S; N]) -> S {
+        let mut sum = (self.coords[0] - point[0]) * (self.coords[0] - point[0]);
+        for i in 1..N {
+            let d = self.coords[i] - point[i];
+            sum = sum + d * d;
+        }
+        sum
+    }
+}
+
+/// An entity tracked at a position in 3D `f32` space - the point type
+/// actually stored in [`RTreeAccess3D`](crate::rtree::rtree3d::RTreeAccess3D)
+/// and [`VPTreeAccess`](crate::vptree::vptree3d::VPTreeAccess).
+pub type EntityPoint3D = SpatialPoint<f32, 3>;
+
+impl EntityPoint3D {
+    /// Convenience accessor for the common `f32`/3D case, where the rest of
+    /// this crate's distance math is expressed in terms of Bevy's `Vec3`
+    /// rather than a raw `[f32; 3]`.
+    pub fn vec(&self) -> Vec3 {
+        Vec3::from_array(self.coords)
+    }
+}
+
+impl From<&(Vec3, Entity)> for EntityPoint3D {
+    fn from((vec, entity): &(Vec3, Entity)) -> Self {
+        SpatialPoint {
+            coords: vec.to_array(),
+            entity: *entity,
+        }
+    }
+}
+
+impl From<(Vec3, Entity)> for EntityPoint3D {
+    fn from((vec, entity): (Vec3, Entity)) -> Self {
+        SpatialPoint {
+            coords: vec.to_array(),
+            entity,
+        }
+    }
+}
+
+impl From<Entity> for EntityPoint3D {
+    /// Builds a placeholder point carrying only `entity`'s identity, with
+    /// no meaningful position - enough for `RTree::remove`'s equality
+    /// check, since [`SpatialPoint`]'s `PartialEq` only compares `entity`.
+    fn from(entity: Entity) -> Self {
+        SpatialPoint { coords: [0.0; 3], entity }
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::Vec3;
+
+/// A distance function over 3D points, decoupled from rstar's built-in
+/// (Euclidean-only) `PointDistance`.
+///
+/// Implementors must satisfy the triangle inequality: `distance(a, c) <=
+/// distance(a, b) + distance(b, c)`. [`crate::vptree::vptree3d::VPTreeAccess`]
+/// relies on this to prune subtrees exactly, and it's what makes a metric
+/// meaningful in the first place (Euclidean, Manhattan and Chebyshev all
+/// satisfy it; an arbitrary per-axis weighting in general does not).
+pub trait Metric: Send + Sync + 'static {
+    /// Distance between two points under this metric.
+    fn distance(a: Vec3, b: Vec3) -> f32;
+
+    /// Squared distance. Defaults to squaring [`Metric::distance`]; override
+    /// when a cheaper comparison is available (e.g. Euclidean distance can
+    /// skip the square root entirely).
+    fn distance_squared(a: Vec3, b: Vec3) -> f32 {
+        Self::distance(a, b).powi(2)
+    }
+}
+
+/// Standard straight-line (L2) distance.
+pub struct Euclidean;
+
+impl Metric for Euclidean {
+    fn distance(a: Vec3, b: Vec3) -> f32 {
+        a.distance(b)
+    }
+
+    fn distance_squared(a: Vec3, b: Vec3) -> f32 {
+        a.distance_squared(b)
+    }
+}
+
+/// Taxicab (L1) distance: the sum of the per-axis differences.
+///
+/// Movement cost on a grid that only allows axis-aligned steps.
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+    fn distance(a: Vec3, b: Vec3) -> f32 {
+        (a.x - b.x).abs() + (a.y - b.y).abs() + (a.z - b.z).abs()
+    }
+}
+
+/// Chessboard (L-infinity) distance: the largest per-axis difference.
+///
+/// Movement cost on a grid where diagonal steps are free, e.g. a king's
+/// move in chess.
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+    fn distance(a: Vec3, b: Vec3) -> f32 {
+        (a.x - b.x).abs().max((a.y - b.y).abs()).max((a.z - b.z).abs())
+    }
+}
+
+/// General Minkowski-`P` distance: `(sum |delta_i|^P)^(1/P)`.
+///
+/// [`Manhattan`] and [`Chebyshev`] are the `P = 1` and `P = infinity`
+/// special cases; use this directly for anything in between.
+pub struct Minkowski<const P: u32>;
+
+impl<const P: u32> Metric for Minkowski<P> {
+    fn distance(a: Vec3, b: Vec3) -> f32 {
+        let sum = (a.x - b.x).abs().powi(P as i32)
+            + (a.y - b.y).abs().powi(P as i32)
+            + (a.z - b.z).abs().powi(P as i32);
+        sum.powf(1.0 / P as f32)
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use bevy::prelude::Entity;
+use rstar::{DefaultParams, RTree, RTreeParams};
+
+use crate::metric::Euclidean;
+
+/// Generic R-tree backed storage shared by the 2D and 3D [`SpatialAccess`]
+/// implementations.
+///
+/// `Point` is the rstar-compatible point type actually stored in the tree
+/// (e.g. [`EntityPoint3D`](crate::common::EntityPoint3D)), `Params` tunes
+/// rstar's own node fan-out, and `M` is the [`Metric`](crate::metric::Metric)
+/// used for queries that aren't already determined by rstar's own internal
+/// (Euclidean) index structure, such as neighbour ordering and radius
+/// pruning.
+pub struct RTreeAccess<TComp, Point, Params: RTreeParams = DefaultParams, M = Euclidean> {
+    pub(crate) tree: RTree<Point, Params>,
+    pub(crate) min_moved: f32,
+    pub(crate) recreate_after: usize,
+    /// Entities physically present in `tree`, tombstoned or not. Lets
+    /// `tombstone_entity` tell "already gone" apart from "newly removed"
+    /// without an O(n) scan of `tree`.
+    pub(crate) present: HashSet<Entity>,
+    /// Tombstoned entities, still physically present in `tree` and skipped
+    /// by queries until the next purge.
+    pub(crate) tombstones: HashSet<Entity>,
+    /// Tombstoned fraction of `tree` at or above which a removal triggers a purge.
+    pub(crate) tombstone_threshold: f32,
+    _comp: PhantomData<TComp>,
+    _metric: PhantomData<M>,
+}
+
+impl<TComp, Point, Params, M> RTreeAccess<TComp, Point, Params, M>
+where
+    Params: RTreeParams,
+{
+    /// Default `tombstone_threshold` for trees created with [`new`](Self::new).
+    pub const DEFAULT_TOMBSTONE_THRESHOLD: f32 = 0.25;
+
+    /// Creates an empty tree.
+    ///
+    /// `min_moved` is the distance an entity must move before its tracked
+    /// position is updated, and `recreate_after` is the number of moved
+    /// entities after which a full rebuild is cheaper than patching each
+    /// one individually.
+    pub fn new(min_moved: f32, recreate_after: usize) -> Self {
+        RTreeAccess {
+            tree: RTree::new(),
+            min_moved,
+            recreate_after,
+            present: HashSet::new(),
+            tombstones: HashSet::new(),
+            tombstone_threshold: Self::DEFAULT_TOMBSTONE_THRESHOLD,
+            _comp: PhantomData,
+            _metric: PhantomData,
+        }
+    }
+
+    /// Sets the tombstoned-fraction threshold that triggers a purge.
+    pub fn with_tombstone_threshold(mut self, threshold: f32) -> Self {
+        self.tombstone_threshold = threshold;
+        self
+    }
+}
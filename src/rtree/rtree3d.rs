@@ -1,50 +1,268 @@
+use std::any::TypeId;
+use std::collections::HashSet;
+#[cfg(feature = "rayon")]
+use std::marker::PhantomData;
+
 use bevy::prelude::*;
-use rstar::{DefaultParams, PointDistance, RTree, RTreeObject, RTreeParams, AABB};
+use bevy::render::primitives::HalfSpace;
+use rstar::{DefaultParams, RTree, RTreeParams, SelectionFunction, AABB};
+
+use crate::{
+    common::EntityPoint3D,
+    metric::{Euclidean, Metric},
+    rtree::common::RTreeAccess,
+    spatial_access::SpatialAccess,
+};
+
+pub type RTreeAccess3D<TComp, Params = DefaultParams, M = Euclidean> =
+    RTreeAccess<TComp, EntityPoint3D, Params, M>;
+
+/// Collects every entity whose axis-aligned per-axis distance to `loc` is
+/// within `radius`.
+///
+/// For any Minkowski-`p` metric with `p >= 1` (which covers [`Euclidean`],
+/// [`Manhattan`](crate::metric::Manhattan), [`Chebyshev`](crate::metric::Chebyshev)
+/// and everything in between), the per-axis difference never exceeds the
+/// overall distance, so this box is guaranteed to contain every point whose
+/// metric distance to `loc` is at most `radius` - it may just also contain
+/// some that are further away, which callers filter out exactly.
+fn locate_in_box<Params>(
+    tree: &RTree<EntityPoint3D, Params>,
+    loc: Vec3,
+    radius: f32,
+) -> impl Iterator<Item = &EntityPoint3D>
+where
+    Params: RTreeParams,
+{
+    let envelope = AABB::from_corners(
+        [loc.x - radius, loc.y - radius, loc.z - radius],
+        [loc.x + radius, loc.y + radius, loc.z + radius],
+    );
+    tree.locate_in_envelope_intersecting(&envelope)
+}
+
+/// Approximate/exact k-nearest search against a single tree, shared by
+/// [`RTreeAccess3D::k_nearest_neighbour_approx`] and, per shard, by
+/// [`ShardedRTreeAccess3D`]. Returns candidates sorted by distance and
+/// truncated to `k`, still carrying their distance so callers merging
+/// several trees' results can re-sort across them.
+fn k_nearest_in_tree<Params, M>(
+    tree: &RTree<EntityPoint3D, Params>,
+    tombstones: &HashSet<Entity>,
+    loc: Vec3,
+    k: usize,
+    epsilon: f32,
+    limit: Option<usize>,
+) -> Vec<(f32, Vec3, Entity)>
+where
+    Params: RTreeParams,
+    M: Metric,
+{
+    if k == 0 || tree.size() == 0 {
+        return Vec::new();
+    }
+
+    // Only rstar's own (Euclidean) nearest_neighbor_iter can answer this
+    // directly, which isn't valid once `M` isn't Euclidean. Instead,
+    // search an expanding axis-aligned box - guaranteed to contain the
+    // true k nearest under `M` (see `locate_in_box`) - doubling the
+    // radius until it's provably large enough to have found them all,
+    // relaxed by `epsilon` and capped by `limit`.
+    let live = tree.size() - tree.iter().filter(|p| tombstones.contains(&p.entity)).count();
+    let mut radius: f32 = 1.0;
+    loop {
+        let found = locate_in_box(tree, loc, radius)
+            .filter(|p| !tombstones.contains(&p.entity))
+            .map(|p| (M::distance(loc, p.vec()), p.vec(), p.entity));
+        let mut candidates: Vec<(f32, Vec3, Entity)> = match limit {
+            Some(limit) => found.take(limit).collect(),
+            None => found.collect(),
+        };
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let found_all = candidates.len() == live;
+        let budget_exhausted = limit.is_some_and(|limit| candidates.len() >= limit) && !found_all;
+        let relaxed_radius = radius * (1.0 + epsilon);
+        let confirmed = candidates
+            .get(k - 1)
+            .is_some_and(|(dist, _, _)| *dist <= relaxed_radius);
+
+        if found_all || confirmed || budget_exhausted {
+            candidates.truncate(k);
+            return candidates;
+        }
+
+        radius *= 2.0;
+    }
+}
 
-use crate::{common::EntityPoint3D, rtree::common::RTreeAccess, spatial_access::SpatialAccess};
+/// Exact/epsilon-relaxed within-distance search against a single tree,
+/// shared by [`RTreeAccess3D::within_distance_approx`] and, per shard, by
+/// [`ShardedRTreeAccess3D`].
+fn within_distance_in_tree<Params, M>(
+    tree: &RTree<EntityPoint3D, Params>,
+    tombstones: &HashSet<Entity>,
+    loc: Vec3,
+    distance: f32,
+    epsilon: f32,
+    limit: Option<usize>,
+) -> Vec<(Vec3, Entity)>
+where
+    Params: RTreeParams,
+    M: Metric,
+{
+    let relaxed = distance * (1.0 + epsilon);
+    let found = locate_in_box(tree, loc, relaxed)
+        .filter(|p| !tombstones.contains(&p.entity) && M::distance(loc, p.vec()) <= distance)
+        .map(|p| (p.vec(), p.entity));
+    match limit {
+        Some(limit) => found.take(limit).collect(),
+        None => found.collect(),
+    }
+}
 
-pub type RTreeAccess3D<TComp, Params = DefaultParams> = RTreeAccess<TComp, EntityPoint3D, Params>;
+/// Returns whether `(min, max)` lies entirely on the negative side of
+/// `half_space`, using the standard "positive vertex" test: the corner of
+/// the box furthest along the half-space's normal is the one most likely to
+/// be inside, so if even that corner is outside, the whole box is.
+fn aabb_outside_half_space(min: Vec3, max: Vec3, half_space: &HalfSpace) -> bool {
+    let normal = half_space.normal();
+    let p_vertex = Vec3::new(
+        if normal.x >= 0.0 { max.x } else { min.x },
+        if normal.y >= 0.0 { max.y } else { min.y },
+        if normal.z >= 0.0 { max.z } else { min.z },
+    );
+    normal.dot(p_vertex) + half_space.d() < 0.0
+}
+
+/// A [`SelectionFunction`] that prunes whatever lies outside a convex region
+/// described by a set of half-spaces (a camera frustum, an OBB, ...), as well
+/// as any tombstoned entity.
+struct HalfSpaceSelection<'a> {
+    half_spaces: &'a [HalfSpace],
+    tombstones: &'a HashSet<Entity>,
+}
+
+impl<'a> SelectionFunction<EntityPoint3D> for HalfSpaceSelection<'a> {
+    fn should_unpack_parent(&self, envelope: &AABB<[f32; 3]>) -> bool {
+        let min = Vec3::from(envelope.lower());
+        let max = Vec3::from(envelope.upper());
+        !self.half_spaces.iter().any(|h| aabb_outside_half_space(min, max, h))
+    }
 
-impl<TComp, Params> SpatialAccess for RTreeAccess3D<TComp, Params>
+    fn should_unpack_leaf(&self, leaf: &EntityPoint3D) -> bool {
+        !self.tombstones.contains(&leaf.entity)
+            && self
+                .half_spaces
+                .iter()
+                .all(|h| h.normal().dot(leaf.vec()) + h.d() >= 0.0)
+    }
+}
+
+impl<TComp, Params, M> SpatialAccess for RTreeAccess3D<TComp, Params, M>
 where
     Params: RTreeParams,
     TComp: Component + Sync + 'static,
+    M: Metric,
 {
     /// The component which this tree tracks.
     type TComp = TComp;
 
-    /// Squared distance between 2 Vec3s.
+    /// Distance between 2 Vec3s, squared, under this tree's [`Metric`].
     ///
     /// For 2d trees this will discard the z component of the Vec3.
     fn distance_squared(&self, loc_a: Vec3, loc_b: Vec3) -> f32 {
-        loc_a.distance_squared(loc_b)
+        M::distance_squared(loc_a, loc_b)
     }
 
     /// Get the nearest neighbour to a position.
     fn nearest_neighbour(&self, loc: Vec3) -> Option<(Vec3, Entity)> {
-        let res = self.tree.nearest_neighbor(&[loc.x, loc.y, loc.z]);
-        res.map(|point| (point.vec, point.entity))
+        self.k_nearest_neighbour(loc, 1).into_iter().next()
     }
 
-    /// Get the `k` neighbours to `loc`
+    /// Get the `k` neighbours to `loc`, ordered by this tree's [`Metric`].
     ///
-    /// If `loc` is the location of a tracked entity, you might want to skip the first.
+    /// If `loc` is the location of a tracked entity, you might want to skip
+    /// the first.
+    ///
+    /// For the default [`Euclidean`] metric this defers to rstar's own
+    /// `nearest_neighbor_iter`, which is lazy and doesn't need the
+    /// expanding-box walk `k_nearest_neighbour_approx` uses for other
+    /// metrics.
     fn k_nearest_neighbour(&self, loc: Vec3, k: usize) -> Vec<(Vec3, Entity)> {
-        return self
-            .tree
-            .nearest_neighbor_iter(&[loc.x, loc.y, loc.z])
-            .take(k)
-            .map(|e| (e.vec, e.entity))
-            .collect::<Vec<(Vec3, Entity)>>();
+        if TypeId::of::<M>() == TypeId::of::<Euclidean>() {
+            return self
+                .tree
+                .nearest_neighbor_iter(&loc.to_array())
+                .filter(|p| !self.tombstones.contains(&p.entity))
+                .take(k)
+                .map(|p| (p.vec(), p.entity))
+                .collect();
+        }
+        self.k_nearest_neighbour_approx(loc, k, 0.0, None)
     }
 
-    /// Get all entities within a certain distance (radius) of `loc`
+    /// Get all entities within a certain distance (radius) of `loc`, under
+    /// this tree's [`Metric`].
     fn within_distance(&self, loc: Vec3, distance: f32) -> Vec<(Vec3, Entity)> {
-        return self
-            .tree
-            .locate_within_distance([loc.x, loc.y, loc.z], distance.powi(2))
-            .map(|e| (e.vec, e.entity))
-            .collect::<Vec<(Vec3, Entity)>>();
+        self.within_distance_approx(loc, distance, 0.0, None)
+    }
+
+    /// Approximate `k` nearest neighbours. See
+    /// [`SpatialAccess::k_nearest_neighbour_approx`] for what `epsilon` and
+    /// `limit` do; with `epsilon = 0.0` and no `limit` this is exactly
+    /// [`k_nearest_neighbour`](Self::k_nearest_neighbour).
+    ///
+    /// `limit` is taken from the box scan in rstar's own (arbitrary, not
+    /// nearest-first) iteration order before sorting, so a tight `limit`
+    /// can make this return candidates well short of the true k-nearest
+    /// even when an exact answer was cheap to reach - it bounds work
+    /// examined, not result quality.
+    fn k_nearest_neighbour_approx(&self, loc: Vec3, k: usize, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        k_nearest_in_tree::<Params, M>(&self.tree, &self.tombstones, loc, k, epsilon, limit)
+            .into_iter()
+            .map(|(_, vec, entity)| (vec, entity))
+            .collect()
+    }
+
+    /// Approximate within-distance query. See
+    /// [`SpatialAccess::within_distance_approx`] for what `epsilon` and
+    /// `limit` do; with `epsilon = 0.0` and no `limit` this is exactly
+    /// [`within_distance`](Self::within_distance).
+    ///
+    /// `epsilon` only widens the box swept to gather candidates; the
+    /// result is still filtered to the exact `distance` radius, so (unlike
+    /// `k_nearest_neighbour_approx`) it never returns a point outside the
+    /// requested radius.
+    fn within_distance_approx(&self, loc: Vec3, distance: f32, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        within_distance_in_tree::<Params, M>(&self.tree, &self.tombstones, loc, distance, epsilon, limit)
+    }
+
+    /// Get all entities inside the axis-aligned box spanned by `min` and
+    /// `max`. A thin wrapper over rstar's own envelope API.
+    fn within_bounding_box(&self, min: Vec3, max: Vec3) -> Vec<(Vec3, Entity)> {
+        let envelope = AABB::from_corners([min.x, min.y, min.z], [max.x, max.y, max.z]);
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .filter(|p| !self.tombstones.contains(&p.entity))
+            .map(|p| (p.vec(), p.entity))
+            .collect()
+    }
+
+    /// Get all entities inside the convex region bounded by `half_spaces`.
+    ///
+    /// Walks the tree with a [`SelectionFunction`] that discards a node as
+    /// soon as its bounding box is fully on the outside of any one
+    /// half-space, so whole subtrees outside the frustum/OBB are skipped
+    /// without ever materialising their points.
+    fn within_half_spaces(&self, half_spaces: &[HalfSpace]) -> Vec<(Vec3, Entity)> {
+        self.tree
+            .locate_with_selection_function(HalfSpaceSelection {
+                half_spaces,
+                tombstones: &self.tombstones,
+            })
+            .map(|p| (p.vec(), p.entity))
+            .collect()
     }
 
     /// Recreates the tree with the provided entity locations/coordinates.
@@ -53,33 +271,45 @@ where
     fn recreate(&mut self, all: Vec<(Vec3, Entity)>) {
         let tree: RTree<EntityPoint3D, Params> =
             RTree::bulk_load_with_params(all.iter().map(|e| e.into()).collect());
+        self.present = all.iter().map(|(_, entity)| *entity).collect();
         self.tree = tree;
+        self.tombstones.clear();
     }
 
     /// Adds a point to the tree.
     ///
     /// Only use if manually updating, the plugin will overwrite changes.
     fn add_point(&mut self, point: (Vec3, Entity)) {
+        if self.tombstones.remove(&point.1) {
+            // Drop the stale tombstoned entry so it isn't revived alongside the new one.
+            if let Some(stale) = self.tree.iter().find(|p| p.entity == point.1).copied() {
+                self.tree.remove(&stale);
+            }
+        }
+        self.present.insert(point.1);
         self.tree.insert(point.into())
     }
 
-    /// Adds a point to the tree.
+    /// Marks a point's entity as removed without touching the tree's
+    /// structure - see [`Self::tombstone_entity`].
     ///
     /// Only use if manually updating, the plugin will overwrite changes.
     fn remove_point(&mut self, point: (Vec3, Entity)) -> bool {
-        self.tree.remove(&point.into()).is_some()
+        self.tombstone_entity(point.1)
     }
 
-    /// Removes a point from the tree.
+    /// Marks `entity` as removed without touching the tree's structure -
+    /// see [`Self::tombstone_entity`].
     ///
     /// Only use if manually updating, the plugin will overwrite changes.
     fn remove_entity(&mut self, entity: Entity) -> bool {
-        self.tree.remove(&entity.into()).is_some()
+        self.tombstone_entity(entity)
     }
 
-    /// Size of the tree
+    /// Count of entities actually still live in the tree, excluding
+    /// tombstoned ones.
     fn size(&self) -> usize {
-        self.tree.size()
+        self.tree.size() - self.tombstones.len()
     }
 
     /// Get the distance after which a entity is updated in the tree
@@ -93,17 +323,419 @@ where
     }
 }
 
-impl RTreeObject for EntityPoint3D {
-    type Envelope = AABB<[f32; 3]>;
+impl<TComp, Params, M> RTreeAccess3D<TComp, Params, M>
+where
+    Params: RTreeParams,
+{
+    /// Marks `entity` as removed without structurally mutating `tree`,
+    /// purging all tombstones once they cross `tombstone_threshold` of the
+    /// tree's raw size. Returns whether `entity` was live and got newly
+    /// tombstoned; an entity that was never in the tree, or already
+    /// tombstoned, is a no-op returning `false`.
+    fn tombstone_entity(&mut self, entity: Entity) -> bool {
+        if !self.present.contains(&entity) || !self.tombstones.insert(entity) {
+            return false;
+        }
+        let total = self.tree.size();
+        if total > 0 && self.tombstones.len() as f32 / total as f32 >= self.tombstone_threshold {
+            self.purge_tombstones();
+        }
+        true
+    }
+
+    /// Rebuilds the tree from its still-live entries, dropping every
+    /// tombstoned one for good.
+    fn purge_tombstones(&mut self) {
+        if self.tombstones.is_empty() {
+            return;
+        }
+        let live: Vec<EntityPoint3D> = self
+            .tree
+            .iter()
+            .filter(|p| !self.tombstones.contains(&p.entity))
+            .copied()
+            .collect();
+        self.present = live.iter().map(|p| p.entity).collect();
+        self.tree = RTree::bulk_load_with_params(live);
+        self.tombstones.clear();
+    }
+}
+
+// `RTreeObject`/`PointDistance` for `EntityPoint3D` (= `SpatialPoint<f32,
+// 3>`) come from the blanket impls in `crate::common`, which is what lets
+// `SpatialPoint` be reused for any scalar/dimension rstar itself supports.
+
+#[cfg(feature = "rayon")]
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+#[cfg(feature = "rayon")]
+impl Axis {
+    fn get(self, vec: Vec3) -> f32 {
+        match self {
+            Axis::X => vec.x,
+            Axis::Y => vec.y,
+            Axis::Z => vec.z,
+        }
+    }
+
+    fn widest(points: &[EntityPoint3D]) -> Axis {
+        let mut min = Vec3::splat(f32::INFINITY);
+        let mut max = Vec3::splat(f32::NEG_INFINITY);
+        for p in points {
+            min = min.min(p.vec());
+            max = max.max(p.vec());
+        }
+        let extent = max - min;
+        if extent.x >= extent.y && extent.x >= extent.z {
+            Axis::X
+        } else if extent.y >= extent.z {
+            Axis::Y
+        } else {
+            Axis::Z
+        }
+    }
+}
+
+/// A sharded R-tree: [`recreate_par`](Self::recreate_par) splits its input
+/// into several roughly-balanced groups and bulk-loads each into its own
+/// independent [`RTree`] on a rayon thread pool, instead of
+/// [`RTreeAccess3D::recreate`]'s single sequential bulk load.
+///
+/// rstar gives no public way to merge independently-built trees into one
+/// without re-paying that same O(n log n) bulk load on a single thread
+/// afterwards, so this doesn't try to produce one tree: every query instead
+/// fans out across all shards and merges their (small, per-shard) results.
+/// That's a real trade - more overhead per query - for construction that
+/// actually runs on more than one thread.
+#[cfg(feature = "rayon")]
+pub struct ShardedRTreeAccess3D<TComp, Params: RTreeParams = DefaultParams, M = Euclidean> {
+    shards: Vec<RTree<EntityPoint3D, Params>>,
+    min_moved: f32,
+    recreate_after: usize,
+    tombstones: HashSet<Entity>,
+    tombstone_threshold: f32,
+    present: HashSet<Entity>,
+    _comp: PhantomData<TComp>,
+    _metric: PhantomData<M>,
+}
+
+#[cfg(feature = "rayon")]
+impl<TComp, Params, M> ShardedRTreeAccess3D<TComp, Params, M>
+where
+    Params: RTreeParams,
+{
+    /// Default `tombstone_threshold` for trees created with [`new`](Self::new).
+    pub const DEFAULT_TOMBSTONE_THRESHOLD: f32 = 0.25;
+
+    /// A default `shard_count` for callers of [`recreate_par`](Self::recreate_par),
+    /// which itself always uses the count it is passed; each shard's bulk
+    /// load is an independent rayon task.
+    pub const DEFAULT_SHARD_COUNT: usize = 8;
+
+    /// Creates an empty tree.
+    pub fn new(min_moved: f32, recreate_after: usize) -> Self {
+        ShardedRTreeAccess3D {
+            shards: Vec::new(),
+            min_moved,
+            recreate_after,
+            tombstones: HashSet::new(),
+            tombstone_threshold: Self::DEFAULT_TOMBSTONE_THRESHOLD,
+            present: HashSet::new(),
+            _comp: PhantomData,
+            _metric: PhantomData,
+        }
+    }
+
+    /// Sets the tombstoned-fraction threshold that triggers a purge.
+    pub fn with_tombstone_threshold(mut self, threshold: f32) -> Self {
+        self.tombstone_threshold = threshold;
+        self
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<TComp, Params, M> ShardedRTreeAccess3D<TComp, Params, M>
+where
+    Params: RTreeParams,
+    TComp: Component + Sync + 'static,
+    M: Metric,
+{
+    /// Rebuilds from `all`, splitting it into `shard_count` groups by
+    /// recursively partitioning on the widest axis and bulk-loading each
+    /// group into its own [`RTree`] concurrently on rayon.
+    pub fn recreate_par(&mut self, all: Vec<(Vec3, Entity)>, shard_count: usize) {
+        let points: Vec<EntityPoint3D> = all.iter().map(|e| e.into()).collect();
+        self.present = points.iter().map(|p| p.entity).collect();
+        self.shards = Self::build_shards(points, shard_count.max(1));
+        self.tombstones.clear();
+    }
+
+    /// Recursively halves `points` on its widest axis until `shard_count`
+    /// groups remain, building each leaf group's tree on its own rayon task
+    /// via [`rayon::join`] so sibling groups build concurrently.
+    fn build_shards(points: Vec<EntityPoint3D>, shard_count: usize) -> Vec<RTree<EntityPoint3D, Params>> {
+        if shard_count <= 1 || points.len() <= 1 {
+            return vec![RTree::bulk_load_with_params(points)];
+        }
+
+        let axis = Axis::widest(&points);
+        let mut points = points;
+        let mid = points.len() / 2;
+        points.select_nth_unstable_by(mid, |a, b| axis.get(a.vec()).total_cmp(&axis.get(b.vec())));
+        let right = points.split_off(mid);
+        let left = points;
+
+        let left_shard_count = shard_count / 2;
+        let right_shard_count = shard_count - left_shard_count;
+        let (mut left_trees, mut right_trees) = rayon::join(
+            || Self::build_shards(left, left_shard_count),
+            || Self::build_shards(right, right_shard_count),
+        );
+        left_trees.append(&mut right_trees);
+        left_trees
+    }
 
-    fn envelope(&self) -> Self::Envelope {
-        AABB::from_point(self.vec.into())
+    /// Marks `entity` as removed without touching any shard's structure,
+    /// purging all tombstones once they cross `tombstone_threshold` of the
+    /// combined shard size. Returns whether `entity` was live and got newly
+    /// tombstoned.
+    fn tombstone_entity(&mut self, entity: Entity) -> bool {
+        if !self.present.contains(&entity) || !self.tombstones.insert(entity) {
+            return false;
+        }
+        let total: usize = self.shards.iter().map(RTree::size).sum();
+        if total > 0 && self.tombstones.len() as f32 / total as f32 >= self.tombstone_threshold {
+            self.purge_tombstones();
+        }
+        true
+    }
+
+    /// Rebuilds every shard from its still-live entries, dropping every
+    /// tombstoned one for good, keeping the current shard count.
+    fn purge_tombstones(&mut self) {
+        if self.tombstones.is_empty() {
+            return;
+        }
+        let shard_count = self.shards.len().max(1);
+        let live: Vec<EntityPoint3D> = self
+            .shards
+            .iter()
+            .flat_map(RTree::iter)
+            .filter(|p| !self.tombstones.contains(&p.entity))
+            .copied()
+            .collect();
+        self.present = live.iter().map(|p| p.entity).collect();
+        self.shards = Self::build_shards(live, shard_count);
+        self.tombstones.clear();
     }
 }
 
-// TODO: currently somewhat duplicating the SpatialAccess distance calculation - how to resolve?
-impl PointDistance for EntityPoint3D {
-    fn distance_2(&self, point: &[f32; 3]) -> f32 {
-        self.vec.distance_squared(Vec3::from_slice(point))
+#[cfg(feature = "rayon")]
+impl<TComp, Params, M> SpatialAccess for ShardedRTreeAccess3D<TComp, Params, M>
+where
+    Params: RTreeParams,
+    TComp: Component + Sync + 'static,
+    M: Metric,
+{
+    type TComp = TComp;
+
+    fn distance_squared(&self, loc_a: Vec3, loc_b: Vec3) -> f32 {
+        M::distance_squared(loc_a, loc_b)
+    }
+
+    fn nearest_neighbour(&self, loc: Vec3) -> Option<(Vec3, Entity)> {
+        self.k_nearest_neighbour(loc, 1).into_iter().next()
+    }
+
+    fn k_nearest_neighbour(&self, loc: Vec3, k: usize) -> Vec<(Vec3, Entity)> {
+        self.k_nearest_neighbour_approx(loc, k, 0.0, None)
+    }
+
+    fn within_distance(&self, loc: Vec3, distance: f32) -> Vec<(Vec3, Entity)> {
+        self.within_distance_approx(loc, distance, 0.0, None)
+    }
+
+    /// Asks every shard for its own `k` nearest, then merges: each shard can
+    /// only prune against its own points, so this over-fetches by up to
+    /// `shard_count * k` candidates before the final sort and truncate.
+    ///
+    /// `limit` is applied to each shard separately, so up to
+    /// `shard_count * limit` entries are examined in all.
+    fn k_nearest_neighbour_approx(&self, loc: Vec3, k: usize, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut candidates: Vec<(f32, Vec3, Entity)> = self
+            .shards
+            .iter()
+            .flat_map(|shard| k_nearest_in_tree::<Params, M>(shard, &self.tombstones, loc, k, epsilon, limit))
+            .collect();
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+        candidates.truncate(k);
+        candidates.into_iter().map(|(_, vec, entity)| (vec, entity)).collect()
+    }
+
+    /// `limit` is applied to each shard separately, so up to
+    /// `shard_count * limit` entries are examined and returned in all.
+    fn within_distance_approx(&self, loc: Vec3, distance: f32, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        self.shards
+            .iter()
+            .flat_map(|shard| within_distance_in_tree::<Params, M>(shard, &self.tombstones, loc, distance, epsilon, limit))
+            .collect()
+    }
+
+    fn within_bounding_box(&self, min: Vec3, max: Vec3) -> Vec<(Vec3, Entity)> {
+        let envelope = AABB::from_corners([min.x, min.y, min.z], [max.x, max.y, max.z]);
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.locate_in_envelope_intersecting(&envelope))
+            .filter(|p| !self.tombstones.contains(&p.entity))
+            .map(|p| (p.vec(), p.entity))
+            .collect()
+    }
+
+    fn within_half_spaces(&self, half_spaces: &[HalfSpace]) -> Vec<(Vec3, Entity)> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                shard.locate_with_selection_function(HalfSpaceSelection {
+                    half_spaces,
+                    tombstones: &self.tombstones,
+                })
+            })
+            .map(|p| (p.vec(), p.entity))
+            .collect()
+    }
+
+    /// Rebuilds as a single shard. Use [`recreate_par`](Self::recreate_par)
+    /// to actually split `all` across several shards on rayon.
+    fn recreate(&mut self, all: Vec<(Vec3, Entity)>) {
+        self.present = all.iter().map(|(_, entity)| *entity).collect();
+        self.shards = vec![RTree::bulk_load_with_params(all.iter().map(|e| e.into()).collect())];
+        self.tombstones.clear();
+    }
+
+    /// Adds a point to whichever shard currently holds the fewest, to keep
+    /// shards roughly balanced as the tree is updated incrementally.
+    fn add_point(&mut self, point: (Vec3, Entity)) {
+        if self.tombstones.remove(&point.1) {
+            // Drop the stale tombstoned entry so it isn't revived alongside the new one.
+            for shard in &mut self.shards {
+                if let Some(stale) = shard.iter().find(|p| p.entity == point.1).copied() {
+                    shard.remove(&stale);
+                    break;
+                }
+            }
+        }
+        self.present.insert(point.1);
+        if self.shards.is_empty() {
+            self.shards.push(RTree::new());
+        }
+        let smallest = self
+            .shards
+            .iter_mut()
+            .min_by_key(|shard| shard.size())
+            .expect("just ensured shards is non-empty");
+        smallest.insert(point.into());
+    }
+
+    fn remove_point(&mut self, point: (Vec3, Entity)) -> bool {
+        self.tombstone_entity(point.1)
+    }
+
+    fn remove_entity(&mut self, entity: Entity) -> bool {
+        self.tombstone_entity(entity)
+    }
+
+    fn size(&self) -> usize {
+        self.shards.iter().map(RTree::size).sum::<usize>() - self.tombstones.len()
+    }
+
+    fn get_min_dist(&self) -> f32 {
+        self.min_moved
+    }
+
+    fn get_recreate_after(&self) -> usize {
+        self.recreate_after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct TestComp;
+
+    fn access() -> RTreeAccess3D<TestComp> {
+        let mut access = RTreeAccess3D::new(0.0, usize::MAX);
+        access.recreate(vec![
+            (Vec3::new(0.0, 0.0, 0.0), Entity::from_raw(0)),
+            (Vec3::new(1.0, 0.0, 0.0), Entity::from_raw(1)),
+            (Vec3::new(2.0, 0.0, 0.0), Entity::from_raw(2)),
+        ]);
+        access
+    }
+
+    #[test]
+    fn removing_an_absent_entity_is_a_noop() {
+        let mut access = access();
+        assert!(!access.remove_entity(Entity::from_raw(99)));
+        assert_eq!(access.size(), 3);
+    }
+
+    #[test]
+    fn size_reflects_tombstones_without_underflowing() {
+        let mut access = access();
+        assert!(access.remove_entity(Entity::from_raw(0)));
+        assert_eq!(access.size(), 2);
+        // Already gone: must not double-count against `tree.size()`.
+        assert!(!access.remove_entity(Entity::from_raw(0)));
+        assert_eq!(access.size(), 2);
+        // Nor panic on an empty tree.
+        let mut empty: RTreeAccess3D<TestComp> = RTreeAccess3D::new(0.0, usize::MAX);
+        assert!(!empty.remove_entity(Entity::from_raw(0)));
+        assert_eq!(empty.size(), 0);
+    }
+
+    #[test]
+    fn purge_drops_tombstones_past_the_threshold() {
+        let mut access = access().with_tombstone_threshold(0.5);
+        access.remove_entity(Entity::from_raw(0));
+        access.remove_entity(Entity::from_raw(1));
+        assert_eq!(access.size(), 1);
+        assert_eq!(access.tombstones.len(), 0, "purge should have cleared tombstones");
+        assert_eq!(access.within_distance(Vec3::new(2.0, 0.0, 0.0), 0.1).len(), 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn sharded_k_nearest_matches_brute_force() {
+        let all: Vec<(Vec3, Entity)> = (0..50)
+            .map(|i| (Vec3::new(i as f32, (i * 7 % 13) as f32, (i * 3 % 11) as f32), Entity::from_raw(i)))
+            .collect();
+
+        let mut access: ShardedRTreeAccess3D<TestComp> = ShardedRTreeAccess3D::new(0.0, usize::MAX);
+        access.recreate_par(all.clone(), 4);
+        assert!(access.shards.len() > 1, "splitting 50 points across 4 shards should produce more than one");
+
+        let loc = Vec3::new(5.0, 5.0, 5.0);
+        let mut got: Vec<Entity> = access.k_nearest_neighbour(loc, 5).into_iter().map(|(_, e)| e).collect();
+        let mut expected: Vec<Entity> = {
+            let mut by_dist: Vec<(f32, Entity)> = all
+                .iter()
+                .map(|(v, e)| (Euclidean::distance(loc, *v), *e))
+                .collect();
+            by_dist.sort_by(|a, b| a.0.total_cmp(&b.0));
+            by_dist.into_iter().take(5).map(|(_, e)| e).collect()
+        };
+        got.sort_by_key(|e| e.index());
+        expected.sort_by_key(|e| e.index());
+        assert_eq!(got, expected);
     }
 }
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy::render::primitives::HalfSpace;
+
+/// Common query interface implemented by every spatial index backend (the
+/// R-tree backend and the vantage-point tree backend).
+pub trait SpatialAccess {
+    /// The component which this tree tracks.
+    type TComp: Component;
+
+    /// Squared distance between 2 Vec3s.
+    fn distance_squared(&self, loc_a: Vec3, loc_b: Vec3) -> f32;
+
+    /// Get the nearest neighbour to a position.
+    fn nearest_neighbour(&self, loc: Vec3) -> Option<(Vec3, Entity)>;
+
+    /// Get the `k` neighbours to `loc`.
+    ///
+    /// If `loc` is the location of a tracked entity, you might want to skip the first.
+    fn k_nearest_neighbour(&self, loc: Vec3, k: usize) -> Vec<(Vec3, Entity)>;
+
+    /// Get all entities within a certain distance (radius) of `loc`.
+    fn within_distance(&self, loc: Vec3, distance: f32) -> Vec<(Vec3, Entity)>;
+
+    /// Get all entities inside the axis-aligned box spanned by `min` and `max`.
+    fn within_bounding_box(&self, min: Vec3, max: Vec3) -> Vec<(Vec3, Entity)>;
+
+    /// Get all entities inside the convex region bounded by `half_spaces`.
+    ///
+    /// A point is inside when it's on the positive side of every half-space,
+    /// i.e. `half_space.normal().dot(point) + half_space.d() >= 0.0` for all
+    /// of them. A camera frustum is six such half-spaces (as in
+    /// [`bevy::render::primitives::Frustum`]); so is an oriented bounding box
+    /// built from its own local axes, which is how this same query also
+    /// serves `within_obb`-style lookups.
+    fn within_half_spaces(&self, half_spaces: &[HalfSpace]) -> Vec<(Vec3, Entity)>;
+
+    /// Approximate version of [`k_nearest_neighbour`](Self::k_nearest_neighbour)
+    /// for large swarms where an exact result every frame isn't worth the
+    /// cost.
+    ///
+    /// `epsilon` relaxes subtree pruning so a candidate is only explored if
+    /// its best possible distance - even after being discounted by a
+    /// `1 / (1 + epsilon)` factor - could still beat the current worst
+    /// accepted result; `0.0` is exact. `limit`, if set, caps how many
+    /// entries are examined, returning the best-so-far once that budget is
+    /// spent.
+    ///
+    /// The default implementation ignores both knobs and defers to the
+    /// exact query; override it where the relaxation can actually prune
+    /// more aggressively.
+    fn k_nearest_neighbour_approx(
+        &self,
+        loc: Vec3,
+        k: usize,
+        _epsilon: f32,
+        _limit: Option<usize>,
+    ) -> Vec<(Vec3, Entity)> {
+        self.k_nearest_neighbour(loc, k)
+    }
+
+    /// Approximate version of [`within_distance`](Self::within_distance); see
+    /// [`k_nearest_neighbour_approx`](Self::k_nearest_neighbour_approx) for
+    /// what `epsilon` and `limit` do.
+    fn within_distance_approx(
+        &self,
+        loc: Vec3,
+        distance: f32,
+        _epsilon: f32,
+        _limit: Option<usize>,
+    ) -> Vec<(Vec3, Entity)> {
+        self.within_distance(loc, distance)
+    }
+
+    /// Recreates the tree with the provided entity locations/coordinates.
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn recreate(&mut self, all: Vec<(Vec3, Entity)>);
+
+    /// Adds a point to the tree.
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn add_point(&mut self, point: (Vec3, Entity));
+
+    /// Removes a point from the tree.
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn remove_point(&mut self, point: (Vec3, Entity)) -> bool;
+
+    /// Removes a point from the tree.
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn remove_entity(&mut self, entity: Entity) -> bool;
+
+    /// Size of the tree
+    fn size(&self) -> usize;
+
+    /// Get the distance after which a entity is updated in the tree
+    fn get_min_dist(&self) -> f32;
+
+    /// Get the amount of entities which moved per frame after which the tree is fully recreated instead of updated.
+    fn get_recreate_after(&self) -> usize;
+}
@@ -0,0 +1,58 @@
+use crate::{common::EntityPoint3D, metric::Metric};
+
+/// A single node of a vantage-point tree.
+///
+/// Leaf nodes (`inside` and `outside` both `None`) hold only a vantage
+/// point. Internal nodes additionally store `radius`, the median distance
+/// from the vantage point to the rest of the points under this node, which
+/// splits them into an `inside` subtree (`distance <= radius`) and an
+/// `outside` subtree (`distance > radius`).
+pub(crate) struct VPNode {
+    pub(crate) vantage: EntityPoint3D,
+    pub(crate) radius: f32,
+    pub(crate) inside: Option<Box<VPNode>>,
+    pub(crate) outside: Option<Box<VPNode>>,
+}
+
+impl VPNode {
+    /// Recursively builds a vantage-point tree over `points`, measuring
+    /// distances with `M`.
+    ///
+    /// An arbitrary point is chosen as the vantage point for each node
+    /// (the first of the remaining points is as good as any other), and
+    /// the rest are partitioned around the median distance to it. This
+    /// keeps the two subtrees roughly balanced without needing a true
+    /// median-of-medians selection. `M` must satisfy the triangle
+    /// inequality for the pruning that later queries do against `radius`
+    /// to be exact.
+    pub(crate) fn build<M: Metric>(mut points: Vec<EntityPoint3D>) -> Option<Box<VPNode>> {
+        if points.is_empty() {
+            return None;
+        }
+
+        let vantage = points.swap_remove(0);
+        if points.is_empty() {
+            return Some(Box::new(VPNode {
+                vantage,
+                radius: 0.0,
+                inside: None,
+                outside: None,
+            }));
+        }
+
+        let mut dists: Vec<f32> = points.iter().map(|p| M::distance(vantage.vec(), p.vec())).collect();
+        dists.sort_by(f32::total_cmp);
+        let radius = dists[dists.len() / 2];
+
+        let (inside, outside): (Vec<EntityPoint3D>, Vec<EntityPoint3D>) = points
+            .into_iter()
+            .partition(|p| M::distance(vantage.vec(), p.vec()) <= radius);
+
+        Some(Box::new(VPNode {
+            vantage,
+            radius,
+            inside: VPNode::build::<M>(inside),
+            outside: VPNode::build::<M>(outside),
+        }))
+    }
+}
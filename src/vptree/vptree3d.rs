@@ -0,0 +1,462 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy::render::primitives::HalfSpace;
+
+use crate::{
+    common::EntityPoint3D,
+    metric::{Euclidean, Metric},
+    spatial_access::SpatialAccess,
+    vptree::common::VPNode,
+};
+
+/// A candidate considered while walking the tree for a nearest-neighbour
+/// query.
+///
+/// Ordered purely by `dist` so a [`BinaryHeap`] of these can be used as a
+/// bounded max-heap: the current worst accepted candidate sits on top and
+/// is the first to be evicted once the heap grows past `k`.
+struct Candidate {
+    dist: f32,
+    vec: Vec3,
+    entity: Entity,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.total_cmp(&other.dist)
+    }
+}
+
+/// A vantage-point tree backed [`SpatialAccess`] implementation, generic
+/// over the [`Metric`] `M` used to measure distances.
+///
+/// Prunes its search using only the triangle inequality rather than
+/// axis-aligned bounds like [`RTreeAccess3D`](crate::rtree::rtree3d::RTreeAccess3D),
+/// making it the right backend for non-Euclidean spaces.
+///
+/// `add_point` rebuilds the tree, since a node's split point depends on
+/// the full set of points beneath it; `remove_point`/`remove_entity`
+/// tombstone instead - see [`Self::tombstone_entity`].
+pub struct VPTreeAccess<TComp, M = Euclidean> {
+    root: Option<Box<VPNode>>,
+    points: Vec<EntityPoint3D>,
+    min_moved: f32,
+    recreate_after: usize,
+    /// Entities physically present in `points`/`root`, tombstoned or not.
+    /// Lets `tombstone_entity` tell "already gone" apart from "newly
+    /// removed" without scanning `points`.
+    present: HashSet<Entity>,
+    /// Tombstoned entities, still physically present in `points`/`root`
+    /// and skipped by queries until the next purge.
+    tombstones: HashSet<Entity>,
+    /// Tombstoned fraction of `points` at or above which a removal triggers a purge.
+    tombstone_threshold: f32,
+    _comp: PhantomData<TComp>,
+    _metric: PhantomData<M>,
+}
+
+impl<TComp, M> VPTreeAccess<TComp, M>
+where
+    M: Metric,
+{
+    /// Default `tombstone_threshold` for trees created with [`new`](Self::new).
+    pub const DEFAULT_TOMBSTONE_THRESHOLD: f32 = 0.25;
+
+    /// Creates an empty tree.
+    ///
+    /// `min_moved` and `recreate_after` are the same tuning knobs as on
+    /// the R-tree backend: the distance an entity must move before its
+    /// tracked position is updated, and the number of moved entities after
+    /// which a full rebuild is cheaper than patching each one.
+    pub fn new(min_moved: f32, recreate_after: usize) -> Self {
+        VPTreeAccess {
+            root: None,
+            points: Vec::new(),
+            min_moved,
+            recreate_after,
+            present: HashSet::new(),
+            tombstones: HashSet::new(),
+            tombstone_threshold: Self::DEFAULT_TOMBSTONE_THRESHOLD,
+            _comp: PhantomData,
+            _metric: PhantomData,
+        }
+    }
+
+    /// Sets the tombstoned-fraction threshold that triggers a purge.
+    pub fn with_tombstone_threshold(mut self, threshold: f32) -> Self {
+        self.tombstone_threshold = threshold;
+        self
+    }
+
+    /// Marks `entity` as removed without rebuilding the tree, purging all
+    /// tombstones once they cross `tombstone_threshold` of `points`.
+    /// Returns whether `entity` was live and got newly tombstoned; an
+    /// entity that was never in the tree, or already tombstoned, is a
+    /// no-op returning `false`.
+    fn tombstone_entity(&mut self, entity: Entity) -> bool {
+        if !self.present.contains(&entity) || !self.tombstones.insert(entity) {
+            return false;
+        }
+        let total = self.points.len();
+        if total > 0 && self.tombstones.len() as f32 / total as f32 >= self.tombstone_threshold {
+            self.purge_tombstones();
+        }
+        true
+    }
+
+    /// Rebuilds the tree from its still-live entries, dropping every
+    /// tombstoned one for good.
+    fn purge_tombstones(&mut self) {
+        if self.tombstones.is_empty() {
+            return;
+        }
+        self.points.retain(|p| !self.tombstones.contains(&p.entity));
+        self.present = self.points.iter().map(|p| p.entity).collect();
+        self.root = VPNode::build::<M>(self.points.clone());
+        self.tombstones.clear();
+    }
+
+    fn k_nearest_query(&self, loc: Vec3, k: usize, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut heap: BinaryHeap<Candidate> = BinaryHeap::with_capacity(k);
+        let mut visited = 0usize;
+        Self::visit_k_nearest(&self.root, loc, k, epsilon, limit, &self.tombstones, &mut visited, &mut heap);
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|c| (c.vec, c.entity))
+            .collect()
+    }
+
+    /// Walks the tree pruning with the triangle inequality, relaxed by
+    /// `epsilon` and capped by `limit`. With `epsilon = 0.0` and no `limit`
+    /// this is an exact k-nearest-neighbour search.
+    fn visit_k_nearest(
+        node: &Option<Box<VPNode>>,
+        loc: Vec3,
+        k: usize,
+        epsilon: f32,
+        limit: Option<usize>,
+        tombstones: &HashSet<Entity>,
+        visited: &mut usize,
+        heap: &mut BinaryHeap<Candidate>,
+    ) {
+        if limit.is_some_and(|limit| *visited >= limit) {
+            return;
+        }
+        let Some(node) = node else { return };
+        *visited += 1;
+        let d = M::distance(loc, node.vantage.vec());
+
+        let worst = heap.peek().map(|c| c.dist);
+        if !tombstones.contains(&node.vantage.entity) && (heap.len() < k || worst.is_some_and(|worst| d < worst)) {
+            if heap.len() == k {
+                heap.pop();
+            }
+            heap.push(Candidate {
+                dist: d,
+                vec: node.vantage.vec(),
+                entity: node.vantage.entity,
+            });
+        }
+
+        // Descend into whichever child `loc` actually falls in first, then
+        // only bother with the other child if it could still beat the
+        // current worst candidate once its lower bound is discounted by
+        // `1 / (1 + epsilon)`.
+        let (near, far) = if d < node.radius {
+            (&node.inside, &node.outside)
+        } else {
+            (&node.outside, &node.inside)
+        };
+
+        Self::visit_k_nearest(near, loc, k, epsilon, limit, tombstones, visited, heap);
+
+        let worst = heap.peek().map(|c| c.dist);
+        let far_lower_bound = (d - node.radius).abs() / (1.0 + epsilon);
+        let should_visit_far = heap.len() < k || worst.is_some_and(|worst| far_lower_bound < worst);
+        if should_visit_far {
+            Self::visit_k_nearest(far, loc, k, epsilon, limit, tombstones, visited, heap);
+        }
+    }
+
+    fn within_distance_query(&self, loc: Vec3, distance: f32, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        let mut out = Vec::new();
+        let mut visited = 0usize;
+        Self::visit_within_distance(&self.root, loc, distance, epsilon, limit, &self.tombstones, &mut visited, &mut out);
+        out
+    }
+
+    fn visit_within_distance(
+        node: &Option<Box<VPNode>>,
+        loc: Vec3,
+        distance: f32,
+        epsilon: f32,
+        limit: Option<usize>,
+        tombstones: &HashSet<Entity>,
+        visited: &mut usize,
+        out: &mut Vec<(Vec3, Entity)>,
+    ) {
+        if limit.is_some_and(|limit| *visited >= limit) {
+            return;
+        }
+        let Some(node) = node else { return };
+        *visited += 1;
+        let d = M::distance(loc, node.vantage.vec());
+
+        if d <= distance && !tombstones.contains(&node.vantage.entity) {
+            out.push((node.vantage.vec(), node.vantage.entity));
+        }
+
+        // `inside` holds points close to the vantage, whose minimum
+        // possible distance from `loc` is `d - radius`; `outside`'s lower
+        // bound is `radius - d`. Prune a child when its lower bound, once
+        // discounted by `1 + epsilon`, still exceeds `distance`;
+        // `epsilon = 0.0` matches the exact rule.
+        if (d - node.radius) / (1.0 + epsilon) <= distance {
+            Self::visit_within_distance(&node.inside, loc, distance, epsilon, limit, tombstones, visited, out);
+        }
+        if (node.radius - d) / (1.0 + epsilon) <= distance {
+            Self::visit_within_distance(&node.outside, loc, distance, epsilon, limit, tombstones, visited, out);
+        }
+    }
+}
+
+impl<TComp, M> SpatialAccess for VPTreeAccess<TComp, M>
+where
+    TComp: Component + Sync + 'static,
+    M: Metric,
+{
+    /// The component which this tree tracks.
+    type TComp = TComp;
+
+    /// Squared distance between 2 Vec3s, under this tree's [`Metric`].
+    fn distance_squared(&self, loc_a: Vec3, loc_b: Vec3) -> f32 {
+        M::distance_squared(loc_a, loc_b)
+    }
+
+    /// Get the nearest neighbour to a position.
+    fn nearest_neighbour(&self, loc: Vec3) -> Option<(Vec3, Entity)> {
+        self.k_nearest_neighbour(loc, 1).into_iter().next()
+    }
+
+    /// Get the `k` neighbours to `loc`, pruning subtrees via the triangle
+    /// inequality instead of an AABB walk.
+    ///
+    /// If `loc` is the location of a tracked entity, you might want to skip
+    /// the first.
+    fn k_nearest_neighbour(&self, loc: Vec3, k: usize) -> Vec<(Vec3, Entity)> {
+        self.k_nearest_neighbour_approx(loc, k, 0.0, None)
+    }
+
+    /// Get all entities within a certain distance (radius) of `loc`.
+    fn within_distance(&self, loc: Vec3, distance: f32) -> Vec<(Vec3, Entity)> {
+        self.within_distance_approx(loc, distance, 0.0, None)
+    }
+
+    /// Approximate `k` nearest neighbours. See
+    /// [`SpatialAccess::k_nearest_neighbour_approx`] for what `epsilon` and
+    /// `limit` do; with `epsilon = 0.0` and no `limit` this is exactly
+    /// [`k_nearest_neighbour`](Self::k_nearest_neighbour).
+    fn k_nearest_neighbour_approx(&self, loc: Vec3, k: usize, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        self.k_nearest_query(loc, k, epsilon, limit)
+    }
+
+    /// Approximate within-distance query. See
+    /// [`SpatialAccess::within_distance_approx`] for what `epsilon` and
+    /// `limit` do; with `epsilon = 0.0` and no `limit` this is exactly
+    /// [`within_distance`](Self::within_distance).
+    fn within_distance_approx(&self, loc: Vec3, distance: f32, epsilon: f32, limit: Option<usize>) -> Vec<(Vec3, Entity)> {
+        self.within_distance_query(loc, distance, epsilon, limit)
+    }
+
+    /// Get all entities inside the axis-aligned box spanned by `min` and
+    /// `max`.
+    ///
+    /// A VP tree's splits are keyed on distance from a vantage point, not
+    /// on any per-axis bound, so there's no equivalent of the R-tree's
+    /// envelope walk to prune with here: every tracked point is checked
+    /// directly.
+    fn within_bounding_box(&self, min: Vec3, max: Vec3) -> Vec<(Vec3, Entity)> {
+        self.points
+            .iter()
+            .filter(|p| !self.tombstones.contains(&p.entity) && p.vec().cmpge(min).all() && p.vec().cmple(max).all())
+            .map(|p| (p.vec(), p.entity))
+            .collect()
+    }
+
+    /// Get all entities inside the convex region bounded by `half_spaces`.
+    ///
+    /// As with [`within_bounding_box`](Self::within_bounding_box), the tree
+    /// offers no structure to prune by here, so this checks every tracked
+    /// point directly.
+    fn within_half_spaces(&self, half_spaces: &[HalfSpace]) -> Vec<(Vec3, Entity)> {
+        self.points
+            .iter()
+            .filter(|p| {
+                !self.tombstones.contains(&p.entity) && half_spaces.iter().all(|h| h.normal().dot(p.vec()) + h.d() >= 0.0)
+            })
+            .map(|p| (p.vec(), p.entity))
+            .collect()
+    }
+
+    /// Rebuilds the tree with the provided entity locations/coordinates.
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn recreate(&mut self, all: Vec<(Vec3, Entity)>) {
+        self.points = all.iter().map(|e| e.into()).collect();
+        self.present = self.points.iter().map(|p| p.entity).collect();
+        self.root = VPNode::build::<M>(self.points.clone());
+        self.tombstones.clear();
+    }
+
+    /// Adds a point to the tree.
+    ///
+    /// A VP tree's splits depend on the full point set below each node, so
+    /// this rebuilds the whole tree; prefer batching additions into
+    /// [`recreate`](Self::recreate) where possible.
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn add_point(&mut self, point: (Vec3, Entity)) {
+        if self.tombstones.remove(&point.1) {
+            // Drop the stale tombstoned entry so it isn't revived alongside the new one.
+            self.points.retain(|p| p.entity != point.1);
+        }
+        self.present.insert(point.1);
+        self.points.push((&point).into());
+        self.root = VPNode::build::<M>(self.points.clone());
+    }
+
+    /// Marks a point's entity as removed without rebuilding the tree - see
+    /// [`Self::tombstone_entity`].
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn remove_point(&mut self, point: (Vec3, Entity)) -> bool {
+        self.tombstone_entity(point.1)
+    }
+
+    /// Marks `entity` as removed without rebuilding the tree - see
+    /// [`Self::tombstone_entity`].
+    ///
+    /// Only use if manually updating, the plugin will overwrite changes.
+    fn remove_entity(&mut self, entity: Entity) -> bool {
+        self.tombstone_entity(entity)
+    }
+
+    /// Count of entities actually still live in the tree, excluding
+    /// tombstoned ones.
+    fn size(&self) -> usize {
+        self.points.len() - self.tombstones.len()
+    }
+
+    /// Get the distance after which a entity is updated in the tree.
+    fn get_min_dist(&self) -> f32 {
+        self.min_moved
+    }
+
+    /// Get the amount of entities which moved per frame after which the tree is fully recreated instead of updated.
+    fn get_recreate_after(&self) -> usize {
+        self.recreate_after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::Manhattan;
+
+    #[derive(Component)]
+    struct TestComp;
+
+    fn points() -> Vec<(Vec3, Entity)> {
+        (0..50)
+            .map(|i| {
+                let i = i as f32;
+                (Vec3::new(i * 0.7, (i * 1.3).sin() * 10.0, i % 5.0), Entity::from_raw(i as u32))
+            })
+            .collect()
+    }
+
+    fn brute_force_k_nearest<M: Metric>(points: &[(Vec3, Entity)], loc: Vec3, k: usize) -> Vec<Entity> {
+        let mut by_dist: Vec<(f32, Entity)> = points.iter().map(|(v, e)| (M::distance(loc, *v), *e)).collect();
+        by_dist.sort_by(|a, b| a.0.total_cmp(&b.0));
+        by_dist.into_iter().take(k).map(|(_, e)| e).collect()
+    }
+
+    fn brute_force_within_distance<M: Metric>(points: &[(Vec3, Entity)], loc: Vec3, distance: f32) -> Vec<Entity> {
+        points.iter().filter(|(v, _)| M::distance(loc, *v) <= distance).map(|(_, e)| *e).collect()
+    }
+
+    #[test]
+    fn k_nearest_matches_brute_force() {
+        let all = points();
+        let mut access: VPTreeAccess<TestComp, Manhattan> = VPTreeAccess::new(0.0, usize::MAX);
+        access.recreate(all.clone());
+
+        let loc = Vec3::new(3.0, -2.0, 1.0);
+        let mut got: Vec<Entity> = access.k_nearest_neighbour(loc, 5).into_iter().map(|(_, e)| e).collect();
+        let mut expected = brute_force_k_nearest::<Manhattan>(&all, loc, 5);
+        got.sort_by_key(|e| e.index());
+        expected.sort_by_key(|e| e.index());
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn within_distance_matches_brute_force() {
+        let all = points();
+        let mut access: VPTreeAccess<TestComp, Manhattan> = VPTreeAccess::new(0.0, usize::MAX);
+        access.recreate(all.clone());
+
+        // `recreate` picks `all[0]` as the root vantage point, so querying
+        // centered on it with a radius well inside its (large) split
+        // radius forces a descent into the `inside` child - the case a
+        // swapped pair of prune conditions would drop entirely.
+        let loc = all[0].0;
+        let mut got: Vec<Entity> = access.within_distance(loc, 6.0).into_iter().map(|(_, e)| e).collect();
+        let mut expected = brute_force_within_distance::<Manhattan>(&all, loc, 6.0);
+        got.sort_by_key(|e| e.index());
+        expected.sort_by_key(|e| e.index());
+        assert!(expected.len() > 1, "test setup should include more than just the vantage itself");
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn removing_an_absent_entity_is_a_noop() {
+        let mut access: VPTreeAccess<TestComp> = VPTreeAccess::new(0.0, usize::MAX);
+        access.recreate(vec![(Vec3::ZERO, Entity::from_raw(0))]);
+        assert!(!access.remove_entity(Entity::from_raw(7)));
+        assert_eq!(access.size(), 1);
+    }
+
+    #[test]
+    fn purge_drops_tombstones_past_the_threshold() {
+        let all = points();
+        let mut access: VPTreeAccess<TestComp> = VPTreeAccess::new(0.0, usize::MAX).with_tombstone_threshold(0.1);
+        access.recreate(all);
+        // 50 points at threshold 0.1 needs 5 tombstones to cross the ratio.
+        for i in 0..5 {
+            access.remove_entity(Entity::from_raw(i));
+        }
+        assert_eq!(access.tombstones.len(), 0, "purge should have cleared tombstones");
+        assert_eq!(access.size(), 45);
+    }
+}